@@ -53,6 +53,12 @@ enum Commands {
         /// Maximum price filter
         #[arg(short, long)]
         max_price: Option<String>,
+
+        /// Path to a compact filter (GCS) published for this --network, keyed by category.
+        /// When --category is also given, skips the network query if the filter indicates
+        /// that category is definitely absent.
+        #[arg(short, long)]
+        filter: Option<String>,
     },
     
     /// Get network information
@@ -118,9 +124,9 @@ async fn main() -> anyhow::Result<()> {
             println!("0x{}", hex::encode(hash));
         }
         
-        Commands::Discover { network, category, max_price } => {
+        Commands::Discover { network, category, max_price, filter } => {
             println!("{}", "Discovering services...".cyan());
-            
+
             if let Some(net) = &network {
                 println!("  Network: {}", net.green());
             }
@@ -130,7 +136,27 @@ async fn main() -> anyhow::Result<()> {
             if let Some(price) = &max_price {
                 println!("  Max Price: {}", price.green());
             }
-            
+
+            if let Some(filter_path) = &filter {
+                let bytes = std::fs::read(filter_path)?;
+                let gcs = GcsFilter::from_bytes(&bytes)?;
+
+                // A facilitator publishes one filter per network (hence `--filter` is paired
+                // with `--network`, which already selects the right file), keyed by category.
+                // `network` itself is never an element of the filter, so it's never tested
+                // here — only `category` is, and only when given: with no category to check,
+                // there's no element we can honestly call "definitely absent".
+                if let Some(cat) = &category {
+                    if !gcs.matches(cat) {
+                        println!(
+                            "\n{}",
+                            "Filter indicates no matching services in this category — skipping network query.".yellow()
+                        );
+                        return Ok(());
+                    }
+                }
+            }
+
             // TODO: Implement service discovery
             println!("\n{}", "Service discovery coming soon!".yellow());
         }
@@ -0,0 +1,250 @@
+//! BIP158-style Golomb-coded set (GCS) compact filter.
+//!
+//! Lets a facilitator publish one filter per network so a client can check whether a
+//! category/asset/service identifier plausibly appears before making a network round-trip.
+//! The network itself is never an element of the filter — it's identified by which filter
+//! file was published and loaded — so callers must build and query elements at that same
+//! granularity (e.g. a bare category string) the facilitator used when calling `build`.
+//! Lookups are probabilistic: `matches` can return a false positive (at a rate tuned by `m`),
+//! but never a false negative.
+
+use crate::errors::{CryptoError, Result};
+use crate::hashing::keccak256;
+
+/// A Golomb-coded set over the elements it was built from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GcsFilter {
+    /// Golomb-Rice parameter: the quotient is coded in unary, divided by `2^p`.
+    p: u8,
+    /// Number of elements the filter was built from.
+    n: u32,
+    /// False-positive rate parameter: elements hash into `[0, n*m)`.
+    m: u32,
+    /// Golomb-Rice coded, sorted, delta-encoded hash set, packed MSB-first.
+    bits: Vec<u8>,
+}
+
+impl GcsFilter {
+    /// Build a filter over `elements`, coding each mapped hash with Golomb-Rice parameter `p`
+    /// and targeting a false-positive rate of `1/m`.
+    pub fn build<T: AsRef<[u8]>>(elements: &[T], p: u8, m: u32) -> Self {
+        let n = elements.len() as u32;
+        let f = u64::from(n) * u64::from(m);
+
+        let mut values: Vec<u64> = elements
+            .iter()
+            .map(|element| hash_to_range(element.as_ref(), f))
+            .collect();
+        values.sort_unstable();
+
+        let mut writer = BitWriter::new();
+        let mut previous = 0u64;
+        for value in values {
+            golomb_rice_encode(&mut writer, value - previous, p);
+            previous = value;
+        }
+
+        Self {
+            p,
+            n,
+            m,
+            bits: writer.into_bytes(),
+        }
+    }
+
+    /// Test whether `element` plausibly appears in the filter. May false-positive; never
+    /// false-negatives.
+    pub fn matches(&self, element: impl AsRef<[u8]>) -> bool {
+        let f = u64::from(self.n) * u64::from(self.m);
+        if f == 0 {
+            return false;
+        }
+        let target = hash_to_range(element.as_ref(), f);
+
+        let mut reader = BitReader::new(&self.bits);
+        let mut running = 0u64;
+        for _ in 0..self.n {
+            let Some(delta) = golomb_rice_decode(&mut reader, self.p) else {
+                break;
+            };
+            running += delta;
+            if running == target {
+                return true;
+            }
+            if running > target {
+                return false;
+            }
+        }
+        false
+    }
+
+    /// True if any of `elements` plausibly appears in the filter.
+    pub fn matches_any<T: AsRef<[u8]>>(&self, elements: &[T]) -> bool {
+        elements.iter().any(|element| self.matches(element))
+    }
+
+    /// Serialize to a flat byte layout (`p`, `n`, `m`, then the coded bitstream) suitable for
+    /// writing to a file and loading back with [`GcsFilter::from_bytes`].
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(9 + self.bits.len());
+        out.push(self.p);
+        out.extend_from_slice(&self.n.to_be_bytes());
+        out.extend_from_slice(&self.m.to_be_bytes());
+        out.extend_from_slice(&self.bits);
+        out
+    }
+
+    /// Parse the layout written by [`GcsFilter::to_bytes`].
+    pub fn from_bytes(data: &[u8]) -> Result<Self> {
+        if data.len() < 9 {
+            return Err(CryptoError::MerkleError(
+                "filter data truncated".to_string(),
+            ));
+        }
+
+        let p = data[0];
+        let n = u32::from_be_bytes(data[1..5].try_into().unwrap());
+        let m = u32::from_be_bytes(data[5..9].try_into().unwrap());
+
+        Ok(Self {
+            p,
+            n,
+            m,
+            bits: data[9..].to_vec(),
+        })
+    }
+}
+
+/// Map `data` into `[0, f)` via the standard 64-bit-hash reduction: `(hash * f) >> 64`.
+fn hash_to_range(data: &[u8], f: u64) -> u64 {
+    let digest = keccak256(data);
+    let hash = u64::from_be_bytes(digest[..8].try_into().unwrap());
+    ((u128::from(hash) * u128::from(f)) >> 64) as u64
+}
+
+fn golomb_rice_encode(writer: &mut BitWriter, value: u64, p: u8) {
+    let quotient = value >> p;
+    for _ in 0..quotient {
+        writer.push_bit(true);
+    }
+    writer.push_bit(false);
+    for shift in (0..p).rev() {
+        writer.push_bit((value >> shift) & 1 == 1);
+    }
+}
+
+fn golomb_rice_decode(reader: &mut BitReader, p: u8) -> Option<u64> {
+    let mut quotient = 0u64;
+    while reader.pop_bit()? {
+        quotient += 1;
+    }
+
+    let mut remainder = 0u64;
+    for _ in 0..p {
+        remainder = (remainder << 1) | u64::from(reader.pop_bit()?);
+    }
+
+    Some((quotient << p) | remainder)
+}
+
+/// Minimal MSB-first bit writer, used to pack Golomb-Rice codes densely.
+struct BitWriter {
+    bytes: Vec<u8>,
+    bit_pos: u8,
+}
+
+impl BitWriter {
+    fn new() -> Self {
+        Self {
+            bytes: Vec::new(),
+            bit_pos: 0,
+        }
+    }
+
+    fn push_bit(&mut self, bit: bool) {
+        if self.bit_pos == 0 {
+            self.bytes.push(0);
+        }
+        if bit {
+            let last = self.bytes.last_mut().unwrap();
+            *last |= 1 << (7 - self.bit_pos);
+        }
+        self.bit_pos = (self.bit_pos + 1) % 8;
+    }
+
+    fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+}
+
+/// Minimal MSB-first bit reader matching [`BitWriter`]'s packing.
+struct BitReader<'a> {
+    bytes: &'a [u8],
+    byte_pos: usize,
+    bit_pos: u8,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self {
+            bytes,
+            byte_pos: 0,
+            bit_pos: 0,
+        }
+    }
+
+    fn pop_bit(&mut self) -> Option<bool> {
+        let byte = *self.bytes.get(self.byte_pos)?;
+        let bit = (byte >> (7 - self.bit_pos)) & 1 == 1;
+
+        self.bit_pos += 1;
+        if self.bit_pos == 8 {
+            self.bit_pos = 0;
+            self.byte_pos += 1;
+        }
+
+        Some(bit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matches_all_built_elements() {
+        let elements = ["base-mainnet:defi:swap", "base-mainnet:oracle:price", "solana-mainnet:nft:mint"];
+        let filter = GcsFilter::build(&elements, 20, 50);
+
+        for element in &elements {
+            assert!(filter.matches(element));
+        }
+    }
+
+    #[test]
+    fn test_absent_element_usually_does_not_match() {
+        let elements: Vec<String> = (0..200).map(|i| format!("service-{i}")).collect();
+        let filter = GcsFilter::build(&elements, 20, 50);
+
+        assert!(!filter.matches("definitely-not-a-built-element"));
+    }
+
+    #[test]
+    fn test_matches_any_true_if_any_element_matches() {
+        let elements = ["base-mainnet:defi:swap"];
+        let filter = GcsFilter::build(&elements, 20, 50);
+
+        assert!(filter.matches_any(&["nonexistent", "base-mainnet:defi:swap"]));
+        assert!(!filter.matches_any(&["nonexistent", "also-nonexistent"]));
+    }
+
+    #[test]
+    fn test_bytes_roundtrip_preserves_matches() {
+        let elements = ["base-mainnet:defi:swap", "base-mainnet:oracle:price"];
+        let filter = GcsFilter::build(&elements, 20, 50);
+
+        let restored = GcsFilter::from_bytes(&filter.to_bytes()).unwrap();
+        assert_eq!(restored, filter);
+        assert!(restored.matches("base-mainnet:oracle:price"));
+    }
+}
@@ -0,0 +1,191 @@
+//! Minimal 256-bit modular arithmetic.
+//!
+//! Not a general-purpose bignum library: it implements only the handful of operations the
+//! [`crate::poseidon`] permutation needs (add, subtract, multiply, exponentiate), all
+//! reduced modulo a caller-supplied prime so different curves' scalar fields can be plugged
+//! into the Poseidon backend.
+
+use std::cmp::Ordering;
+
+/// A 256-bit unsigned integer as four little-endian 64-bit limbs.
+pub type Limbs = [u64; 4];
+
+pub fn from_be_bytes(bytes: &[u8; 32]) -> Limbs {
+    let mut limbs = [0u64; 4];
+    for (i, limb) in limbs.iter_mut().enumerate() {
+        let start = (3 - i) * 8;
+        let mut chunk = [0u8; 8];
+        chunk.copy_from_slice(&bytes[start..start + 8]);
+        *limb = u64::from_be_bytes(chunk);
+    }
+    limbs
+}
+
+pub fn to_be_bytes(limbs: &Limbs) -> [u8; 32] {
+    let mut bytes = [0u8; 32];
+    for (i, limb) in limbs.iter().enumerate() {
+        let start = (3 - i) * 8;
+        bytes[start..start + 8].copy_from_slice(&limb.to_be_bytes());
+    }
+    bytes
+}
+
+pub fn cmp(a: &Limbs, b: &Limbs) -> Ordering {
+    for i in (0..4).rev() {
+        match a[i].cmp(&b[i]) {
+            Ordering::Equal => continue,
+            other => return other,
+        }
+    }
+    Ordering::Equal
+}
+
+fn add_raw(a: &Limbs, b: &Limbs) -> (Limbs, bool) {
+    let mut out = [0u64; 4];
+    let mut carry = 0u128;
+    for i in 0..4 {
+        let sum = a[i] as u128 + b[i] as u128 + carry;
+        out[i] = sum as u64;
+        carry = sum >> 64;
+    }
+    (out, carry != 0)
+}
+
+fn sub_raw(a: &Limbs, b: &Limbs) -> (Limbs, bool) {
+    let mut out = [0u64; 4];
+    let mut borrow = 0i128;
+    for i in 0..4 {
+        let diff = a[i] as i128 - b[i] as i128 - borrow;
+        if diff < 0 {
+            out[i] = (diff + (1i128 << 64)) as u64;
+            borrow = 1;
+        } else {
+            out[i] = diff as u64;
+            borrow = 0;
+        }
+    }
+    (out, borrow != 0)
+}
+
+/// Reduce `a` into `[0, modulus)`, assuming `a < 2 * modulus` (true for every value this
+/// module produces, since inputs are at most doubled before a reduction).
+fn reduce_once(a: Limbs, modulus: &Limbs) -> Limbs {
+    if cmp(&a, modulus) != Ordering::Less {
+        sub_raw(&a, modulus).0
+    } else {
+        a
+    }
+}
+
+pub fn add_mod(a: &Limbs, b: &Limbs, modulus: &Limbs) -> Limbs {
+    let (sum, overflowed) = add_raw(a, b);
+    if overflowed {
+        // sum wrapped past 2^256; subtracting modulus once always brings it back in range
+        // because both inputs were already < modulus.
+        sub_raw(&sum, modulus).0
+    } else {
+        reduce_once(sum, modulus)
+    }
+}
+
+pub fn sub_mod(a: &Limbs, b: &Limbs, modulus: &Limbs) -> Limbs {
+    if cmp(a, b) == Ordering::Less {
+        let (sum, _) = add_raw(a, modulus);
+        sub_raw(&sum, b).0
+    } else {
+        sub_raw(a, b).0
+    }
+}
+
+/// Reduce an arbitrary 256-bit value into `[0, modulus)` by repeated subtraction. Only
+/// efficient for `modulus` close to 2^256 (true of every curve scalar field in practice).
+pub fn reduce(a: &Limbs, modulus: &Limbs) -> Limbs {
+    let mut value = *a;
+    while cmp(&value, modulus) != Ordering::Less {
+        value = sub_raw(&value, modulus).0;
+    }
+    value
+}
+
+fn sub_small(a: &Limbs, small: u64) -> Limbs {
+    sub_raw(a, &[small, 0, 0, 0]).0
+}
+
+/// Double-and-add multiplication: avoids needing a 512-bit intermediate product.
+pub fn mul_mod(a: &Limbs, b: &Limbs, modulus: &Limbs) -> Limbs {
+    let mut result = [0u64; 4];
+    let mut addend = *a;
+
+    for &limb in b.iter() {
+        for bit in 0..64 {
+            if (limb >> bit) & 1 == 1 {
+                result = add_mod(&result, &addend, modulus);
+            }
+            addend = add_mod(&addend, &addend, modulus);
+        }
+    }
+
+    result
+}
+
+/// Square-and-multiply exponentiation, `base^exponent mod modulus`.
+pub fn pow_mod(base: &Limbs, exponent: &Limbs, modulus: &Limbs) -> Limbs {
+    let mut result = [1u64, 0, 0, 0];
+    let mut base = *base;
+
+    for &limb in exponent.iter() {
+        for bit in 0..64 {
+            if (limb >> bit) & 1 == 1 {
+                result = mul_mod(&result, &base, modulus);
+            }
+            base = mul_mod(&base, &base, modulus);
+        }
+    }
+
+    result
+}
+
+/// Modular inverse via Fermat's little theorem (`a^(modulus-2) mod modulus`); only valid
+/// for prime `modulus` and non-zero `a`.
+pub fn inv_mod(a: &Limbs, modulus: &Limbs) -> Limbs {
+    pow_mod(a, &sub_small(modulus, 2), modulus)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SMALL_PRIME: Limbs = [97, 0, 0, 0];
+
+    #[test]
+    fn test_add_sub_roundtrip() {
+        let a = [40, 0, 0, 0];
+        let b = [90, 0, 0, 0];
+        let sum = add_mod(&a, &b, &SMALL_PRIME);
+        assert_eq!(sum, [33, 0, 0, 0]); // (40 + 90) mod 97 = 33
+
+        let back = sub_mod(&sum, &b, &SMALL_PRIME);
+        assert_eq!(back, a);
+    }
+
+    #[test]
+    fn test_mul_mod_small_prime() {
+        let a = [11, 0, 0, 0];
+        let b = [13, 0, 0, 0];
+        assert_eq!(mul_mod(&a, &b, &SMALL_PRIME), [143 % 97, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_inverse_roundtrips_to_one() {
+        let a = [5, 0, 0, 0];
+        let inverse = inv_mod(&a, &SMALL_PRIME);
+        assert_eq!(mul_mod(&a, &inverse, &SMALL_PRIME), [1, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_bytes_roundtrip() {
+        let bytes = [0x07u8; 32];
+        let limbs = from_be_bytes(&bytes);
+        assert_eq!(to_be_bytes(&limbs), bytes);
+    }
+}
@@ -0,0 +1,99 @@
+//! Pluggable hash backends for [`crate::merkle::MerkleTree`], so the same tree
+//! implementation can produce roots for EVM chains (Keccak-256) as well as ZK-friendly or
+//! non-EVM networks (Poseidon over BN254/Starknet-style fields).
+
+use crate::hashing::keccak256;
+use crate::poseidon::{self, PoseidonConfig};
+
+/// A hash function family usable as the combining function for a [`crate::merkle::MerkleTree`].
+pub trait HashBackend {
+    /// Combine two sibling nodes into their parent hash.
+    fn hash_pair(&self, left: &[u8; 32], right: &[u8; 32]) -> [u8; 32];
+
+    /// Hash raw leaf data into the tree's 32-byte leaf commitment.
+    fn hash_leaf(&self, data: &[u8]) -> [u8; 32];
+}
+
+/// The Keccak-256 backend: `MerkleTree`'s original, EVM-compatible behavior. Siblings are
+/// sorted before hashing so proofs don't depend on left/right position.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Keccak256Backend;
+
+impl HashBackend for Keccak256Backend {
+    fn hash_pair(&self, left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+        let (left, right) = if left <= right {
+            (left, right)
+        } else {
+            (right, left)
+        };
+
+        let mut combined = Vec::with_capacity(64);
+        combined.extend_from_slice(left);
+        combined.extend_from_slice(right);
+        keccak256(&combined)
+    }
+
+    fn hash_leaf(&self, data: &[u8]) -> [u8; 32] {
+        keccak256(data)
+    }
+}
+
+/// A Poseidon backend, for ZK-friendly or Starknet-style roots. Unlike the Keccak backend,
+/// siblings are combined in their given (positional) order rather than sorted by value.
+#[derive(Debug, Clone)]
+pub struct PoseidonBackend {
+    config: PoseidonConfig,
+}
+
+impl PoseidonBackend {
+    /// Build a backend from an explicit configuration, e.g. an audited parameter set for a
+    /// specific curve.
+    pub fn new(config: PoseidonConfig) -> Self {
+        Self { config }
+    }
+}
+
+impl Default for PoseidonBackend {
+    /// Uses [`PoseidonConfig::unaudited_bn254_placeholder`] — not interoperable with any real
+    /// Poseidon instance; see its docs before relying on this for anything beyond prototyping
+    /// an order-sensitive backend.
+    fn default() -> Self {
+        Self::new(PoseidonConfig::unaudited_bn254_placeholder())
+    }
+}
+
+impl HashBackend for PoseidonBackend {
+    fn hash_pair(&self, left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+        poseidon::hash_two(left, right, &self.config)
+    }
+
+    /// Only the first 32 bytes of `data` are hashed: shorter input is zero-padded on the
+    /// right, and any bytes past the 32nd are ignored.
+    fn hash_leaf(&self, data: &[u8]) -> [u8; 32] {
+        let mut padded = [0u8; 32];
+        let len = data.len().min(32);
+        padded[..len].copy_from_slice(&data[..len]);
+        poseidon::hash_one(&padded, &self.config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keccak_backend_sorts_siblings() {
+        let backend = Keccak256Backend;
+        let a = [1u8; 32];
+        let b = [2u8; 32];
+        assert_eq!(backend.hash_pair(&a, &b), backend.hash_pair(&b, &a));
+    }
+
+    #[test]
+    fn test_poseidon_backend_is_order_sensitive() {
+        let backend = PoseidonBackend::default();
+        let a = [1u8; 32];
+        let b = [2u8; 32];
+        assert_ne!(backend.hash_pair(&a, &b), backend.hash_pair(&b, &a));
+    }
+}
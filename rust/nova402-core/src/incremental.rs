@@ -0,0 +1,271 @@
+//! Incremental, append-only Merkle tree with O(log n) root computation.
+//!
+//! Suited to a running commitment log (e.g. settled x402 payments), where leaves only ever
+//! get appended and retaining the full leaf set isn't necessary: only the "frontier" — the
+//! filled-subtree root at each height that's still waiting for its right sibling — needs to
+//! be kept.
+
+use serde::{Deserialize, Serialize};
+
+use crate::errors::{CryptoError, Result};
+use crate::hash_backend::{HashBackend, Keccak256Backend};
+
+/// The frontier of an [`IncrementalMerkleTree`]: at most `depth` filled-subtree roots,
+/// indexed by height, plus how many leaves have been appended. Serializable so the tree can
+/// be persisted and resumed across process restarts.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Frontier {
+    pub filled: Vec<Option<[u8; 32]>>,
+    pub leaf_count: u64,
+}
+
+/// Append-only Merkle tree that retains only the frontier, not the full leaf set. Generic
+/// over its combining [`HashBackend`], defaulting to [`Keccak256Backend`].
+pub struct IncrementalMerkleTree<H: HashBackend = Keccak256Backend> {
+    depth: usize,
+    frontier: Frontier,
+    empty_hashes: Vec<[u8; 32]>,
+    backend: H,
+}
+
+impl IncrementalMerkleTree<Keccak256Backend> {
+    /// Create a new, empty tree that can hold up to `2^depth - 1` leaves, using the original
+    /// Keccak-256 backend.
+    pub fn new(depth: usize) -> Self {
+        Self::with_backend(depth, Keccak256Backend)
+    }
+}
+
+impl<H: HashBackend> IncrementalMerkleTree<H> {
+    /// Create a new, empty tree using an explicit hash backend.
+    pub fn with_backend(depth: usize, backend: H) -> Self {
+        let empty_hashes = Self::precompute_empty_hashes(depth, &backend);
+        Self {
+            depth,
+            frontier: Frontier {
+                filled: vec![None; depth],
+                leaf_count: 0,
+            },
+            empty_hashes,
+            backend,
+        }
+    }
+
+    /// Resume a tree from a previously persisted [`Frontier`] snapshot.
+    ///
+    /// Validates that `leaf_count` is within capacity and that each slot's presence matches
+    /// the corresponding bit of `leaf_count`, so a corrupt or hostile snapshot is rejected
+    /// here rather than panicking later in [`IncrementalMerkleTree::root`].
+    pub fn from_frontier(depth: usize, frontier: Frontier, backend: H) -> Result<Self> {
+        if frontier.filled.len() != depth {
+            return Err(CryptoError::MerkleError(format!(
+                "frontier has {} levels, expected {}",
+                frontier.filled.len(),
+                depth
+            )));
+        }
+
+        if frontier.leaf_count > (1u64 << depth) - 1 {
+            return Err(CryptoError::MerkleError(format!(
+                "frontier leaf_count {} exceeds capacity of a depth-{} tree",
+                frontier.leaf_count, depth
+            )));
+        }
+
+        for (height, slot) in frontier.filled.iter().enumerate() {
+            let bit_set = (frontier.leaf_count >> height) & 1 == 1;
+            if slot.is_some() != bit_set {
+                return Err(CryptoError::MerkleError(format!(
+                    "frontier slot at height {} is {} but leaf_count {} implies it should be {}",
+                    height,
+                    if slot.is_some() { "filled" } else { "empty" },
+                    frontier.leaf_count,
+                    if bit_set { "filled" } else { "empty" }
+                )));
+            }
+        }
+
+        Ok(Self {
+            depth,
+            frontier,
+            empty_hashes: Self::precompute_empty_hashes(depth, &backend),
+            backend,
+        })
+    }
+
+    /// Precompute the "empty subtree" hash at every height: `empty[0]` is the zero leaf,
+    /// `empty[h+1]` is that subtree combined with itself.
+    fn precompute_empty_hashes(depth: usize, backend: &H) -> Vec<[u8; 32]> {
+        let mut hashes = Vec::with_capacity(depth + 1);
+        hashes.push([0u8; 32]);
+
+        for height in 0..depth {
+            let subtree = hashes[height];
+            hashes.push(backend.hash_pair(&subtree, &subtree));
+        }
+
+        hashes
+    }
+
+    /// Append a leaf, updating the frontier in O(depth).
+    ///
+    /// A depth-`d` tree holds at most `2^d - 1` leaves: the all-bits-set count is the last
+    /// one a `d`-height frontier can represent without aliasing back to an empty tree.
+    pub fn append(&mut self, leaf: [u8; 32]) -> Result<()> {
+        if self.frontier.leaf_count >= (1u64 << self.depth) - 1 {
+            return Err(CryptoError::MerkleError(format!(
+                "tree of depth {} is full",
+                self.depth
+            )));
+        }
+
+        let mut node = leaf;
+
+        for height in 0..self.depth {
+            match self.frontier.filled[height].take() {
+                Some(left) => {
+                    // This height already had a pending left sibling: combine, clear the
+                    // slot, and carry the result up to be matched against the next height.
+                    node = self.backend.hash_pair(&left, &node);
+                }
+                None => {
+                    self.frontier.filled[height] = Some(node);
+                    self.frontier.leaf_count += 1;
+                    return Ok(());
+                }
+            }
+        }
+
+        // The carry resolved all the way to the top: this leaf completed a perfectly full
+        // subtree, so there's no pending left sibling to record at any height.
+        self.frontier.leaf_count += 1;
+        Ok(())
+    }
+
+    /// Compute the current root, filling in missing right siblings with the precomputed
+    /// empty-subtree hash for their height.
+    pub fn root(&self) -> [u8; 32] {
+        let mut node = self.empty_hashes[0];
+
+        for height in 0..self.depth {
+            node = if (self.frontier.leaf_count >> height) & 1 == 1 {
+                self.backend.hash_pair(
+                    self.frontier.filled[height]
+                        .as_ref()
+                        .expect("leaf_count bit set implies a filled slot at this height"),
+                    &node,
+                )
+            } else {
+                self.backend.hash_pair(&node, &self.empty_hashes[height])
+            };
+        }
+
+        node
+    }
+
+    /// Number of leaves appended so far.
+    pub fn leaf_count(&self) -> u64 {
+        self.frontier.leaf_count
+    }
+
+    /// Snapshot the current frontier for persistence.
+    pub fn frontier(&self) -> &Frontier {
+        &self.frontier
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hashing::keccak256;
+    use crate::merkle::MerkleTree;
+
+    #[test]
+    fn test_empty_tree_root_matches_precomputed_empty_hash() {
+        let tree = IncrementalMerkleTree::new(4);
+        assert_eq!(tree.root(), tree.empty_hashes[4]);
+    }
+
+    #[test]
+    fn test_matches_zero_padded_static_tree() {
+        // A depth-3 frontier pads its unfilled right siblings with the empty-subtree hash,
+        // which is exactly what a static tree computes if padded with zero leaves up to its
+        // full 2^depth width.
+        let leaves: Vec<[u8; 32]> = (0..5)
+            .map(|i| keccak256(format!("tx{}", i).as_bytes()))
+            .collect();
+
+        let mut incremental = IncrementalMerkleTree::new(3);
+        for &leaf in &leaves {
+            incremental.append(leaf).unwrap();
+        }
+
+        let mut padded = leaves;
+        padded.resize(8, [0u8; 32]);
+        let static_tree = MerkleTree::new(padded).unwrap();
+
+        assert_eq!(incremental.root(), static_tree.root());
+    }
+
+    #[test]
+    fn test_root_changes_after_each_append() {
+        let mut tree = IncrementalMerkleTree::new(8);
+        let empty_root = tree.root();
+
+        tree.append(keccak256(b"payment-1")).unwrap();
+        let root_after_one = tree.root();
+        assert_ne!(root_after_one, empty_root);
+
+        tree.append(keccak256(b"payment-2")).unwrap();
+        let root_after_two = tree.root();
+        assert_ne!(root_after_two, root_after_one);
+
+        assert_eq!(tree.leaf_count(), 2);
+    }
+
+    #[test]
+    fn test_frontier_roundtrip_resumes_same_root() {
+        let mut tree = IncrementalMerkleTree::new(8);
+        tree.append(keccak256(b"payment-1")).unwrap();
+        tree.append(keccak256(b"payment-2")).unwrap();
+        tree.append(keccak256(b"payment-3")).unwrap();
+
+        let snapshot = tree.frontier().clone();
+        let resumed = IncrementalMerkleTree::from_frontier(8, snapshot, Keccak256Backend).unwrap();
+
+        assert_eq!(resumed.root(), tree.root());
+        assert_eq!(resumed.leaf_count(), tree.leaf_count());
+    }
+
+    #[test]
+    fn test_from_frontier_rejects_leaf_count_inconsistent_with_filled_slots() {
+        // leaf_count = 1 implies height 0 should be filled and no other height should be, but
+        // this snapshot claims nothing is filled at all.
+        let corrupt = Frontier {
+            filled: vec![None; 4],
+            leaf_count: 1,
+        };
+
+        assert!(IncrementalMerkleTree::from_frontier(4, corrupt, Keccak256Backend).is_err());
+    }
+
+    #[test]
+    fn test_from_frontier_rejects_leaf_count_over_capacity() {
+        let corrupt = Frontier {
+            filled: vec![None; 2],
+            leaf_count: 4, // depth 2 holds at most 2^2 - 1 = 3 leaves
+        };
+
+        assert!(IncrementalMerkleTree::from_frontier(2, corrupt, Keccak256Backend).is_err());
+    }
+
+    #[test]
+    fn test_append_fails_once_full() {
+        // Depth 2 holds at most 2^2 - 1 = 3 leaves.
+        let mut tree = IncrementalMerkleTree::new(2);
+        tree.append(keccak256(b"a")).unwrap();
+        tree.append(keccak256(b"b")).unwrap();
+        tree.append(keccak256(b"c")).unwrap();
+        assert!(tree.append(keccak256(b"d")).is_err());
+    }
+}
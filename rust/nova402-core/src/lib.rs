@@ -6,7 +6,11 @@
 //! - Keccak-256 and SHA3 hashing
 //! - EVM (secp256k1) signature creation and verification
 //! - Payment data encoding and decoding
-//! - Merkle tree construction and proof generation
+//! - Merkle tree construction and proof generation, over pluggable hash backends
+//!   (Keccak-256 for EVM, Poseidon for ZK/Starknet)
+//! - Incremental, append-only Merkle trees for commitment logs
+//! - Merkle Patricia Trie proof verification
+//! - BIP158-style compact filters for offline service discovery
 //! - Address and payment validation
 //!
 //! ## Features
@@ -36,8 +40,15 @@
 
 pub mod encoding;
 pub mod errors;
+pub mod filter;
+pub mod fr;
+pub mod hash_backend;
 pub mod hashing;
+pub mod incremental;
 pub mod merkle;
+pub mod mpt;
+pub mod poseidon;
+pub mod rlp;
 pub mod signature;
 pub mod validation;
 
@@ -48,13 +59,19 @@ pub use encoding::{
     PaymentPayload, PaymentRequirements,
 };
 pub use errors::{CryptoError, Result};
+pub use filter::GcsFilter;
 pub use hashing::{
     double_keccak256, hash_concat, hash_payment_data, hash_string, keccak256, keccak256_hash,
     sha256, sha3_256,
 };
+pub use hash_backend::{HashBackend, Keccak256Backend, PoseidonBackend};
+pub use incremental::{Frontier, IncrementalMerkleTree};
 pub use merkle::{
-    compute_merkle_root, generate_merkle_proof, verify_merkle_proof, MerkleTree,
+    compute_merkle_root, generate_merkle_proof, verify_merkle_proof, verify_multiproof,
+    MerkleTree, MultiProofFlag, Position, ProofStep,
 };
+pub use mpt::{verify_account_proof, verify_storage_proof, AccountState};
+pub use poseidon::PoseidonConfig;
 pub use signature::{recover_signer, sign_payment, verify_signature, SignatureComponents};
 pub use validation::{
     is_payment_expired, is_payment_valid_now, validate_address, validate_amount,
@@ -1,17 +1,149 @@
 //! Merkle tree implementation for x402 protocol
 
 use crate::errors::{CryptoError, Result};
+use crate::hash_backend::{HashBackend, Keccak256Backend};
 use crate::hashing::keccak256;
 
-/// Merkle tree for efficient payment verification
-pub struct MerkleTree {
+/// Per-step instruction for [`verify_multiproof`], produced by
+/// [`MerkleTree::generate_multiproof`].
+///
+/// A plain two-state (combine-with-known / combine-with-proof) flag is only sufficient for
+/// perfect, power-of-two-sized trees. This tree promotes a layer's trailing unpaired node
+/// unchanged (see `with_backend`), so a third state is needed to thread a known, promoted
+/// node across layers without hashing it against anything.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MultiProofFlag {
+    /// Both operands of this combine step are already known (from `leaves` or an earlier
+    /// step's output).
+    Known,
+    /// One operand is known; the other is supplied from `proof`.
+    Proof,
+    /// A trailing node with no sibling was promoted unchanged one layer up; carry the next
+    /// known value through as-is, with no combine.
+    Carry,
+}
+
+/// Which side of a combine step a proof's sibling sits on.
+///
+/// Needed because a promoted (unpaired) node can shift a leaf's parity relative to its layer
+/// without a sibling being recorded for that layer at all — inferring left/right from the
+/// leaf's original index drifts out of sync the moment that happens (it only happens to work
+/// on perfect, power-of-two-sized trees). Recording the side explicitly at generation time
+/// sidesteps the inference entirely.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Position {
+    /// The sibling is the left operand; the node being proven is the right operand.
+    Left,
+    /// The sibling is the right operand; the node being proven is the left operand.
+    Right,
+}
+
+/// One step of a Merkle proof: a sibling hash plus its [`Position`] relative to the node being
+/// proven.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProofStep {
+    pub sibling: [u8; 32],
+    pub position: Position,
+}
+
+/// Merkle tree for efficient payment verification, generic over its combining
+/// [`HashBackend`]. Defaults to [`Keccak256Backend`] (the original, EVM-compatible
+/// behavior) so existing callers of `MerkleTree::new` are unaffected.
+pub struct MerkleTree<H: HashBackend = Keccak256Backend> {
     leaves: Vec<[u8; 32]>,
     layers: Vec<Vec<[u8; 32]>>,
+    backend: H,
+}
+
+impl MerkleTree<Keccak256Backend> {
+    /// Create a new Merkle tree from leaves, using the original Keccak-256 backend.
+    pub fn new(leaves: Vec<[u8; 32]>) -> Result<Self> {
+        Self::with_backend(leaves, Keccak256Backend)
+    }
+
+    /// Generate a multiproof covering a batch of leaves at `indices`.
+    ///
+    /// Returns `(proof, proof_flags)`, an OpenZeppelin-style multiproof extended with a
+    /// [`MultiProofFlag::Carry`] step for every promoted node on the path that's part of the
+    /// known set (see [`MultiProofFlag`]). `proof` holds the sibling hashes needed whenever
+    /// only one side of a combine step is already known. Pass the same leaves back to
+    /// [`verify_multiproof`] in ascending tree order.
+    ///
+    /// Only available on the Keccak backend: [`verify_multiproof`] combines sibling pairs with
+    /// [`combine_sorted`] (sorted-pair Keccak), so a multiproof generated over a
+    /// [`crate::hash_backend::PoseidonBackend`] tree could never be verified correctly.
+    pub fn generate_multiproof(
+        &self,
+        indices: &[usize],
+    ) -> Result<(Vec<[u8; 32]>, Vec<MultiProofFlag>)> {
+        if indices.is_empty() {
+            return Err(CryptoError::MerkleError(
+                "Cannot generate multiproof for no indices".to_string(),
+            ));
+        }
+
+        for &index in indices {
+            if index >= self.leaves.len() {
+                return Err(CryptoError::MerkleError(format!(
+                    "Index {} out of bounds for {} leaves",
+                    index,
+                    self.leaves.len()
+                )));
+            }
+        }
+
+        let mut known = vec![false; self.leaves.len()];
+        for &index in indices {
+            known[index] = true;
+        }
+
+        let mut proof = Vec::new();
+        let mut proof_flags = Vec::new();
+
+        for layer_idx in 0..self.layers.len() - 1 {
+            let layer = &self.layers[layer_idx];
+            let mut next_known = Vec::with_capacity(layer.len() / 2 + 1);
+
+            let mut i = 0;
+            while i < layer.len() {
+                if i + 1 < layer.len() {
+                    let left_matched = known[i];
+                    let right_matched = known[i + 1];
+
+                    if left_matched && right_matched {
+                        proof_flags.push(MultiProofFlag::Known);
+                    } else if left_matched {
+                        proof_flags.push(MultiProofFlag::Proof);
+                        proof.push(layer[i + 1]);
+                    } else if right_matched {
+                        proof_flags.push(MultiProofFlag::Proof);
+                        proof.push(layer[i]);
+                    }
+
+                    next_known.push(left_matched || right_matched);
+                    i += 2;
+                } else {
+                    // Odd leaf, promoted unchanged. If it's part of the known set, emit an
+                    // explicit carry step so the verifier can track it across the
+                    // promotion; an unmatched promoted node needs no proof-side bookkeeping.
+                    if known[i] {
+                        proof_flags.push(MultiProofFlag::Carry);
+                    }
+                    next_known.push(known[i]);
+                    i += 1;
+                }
+            }
+
+            known = next_known;
+        }
+
+        Ok((proof, proof_flags))
+    }
 }
 
-impl MerkleTree {
-    /// Create a new Merkle tree from leaves
-    pub fn new(mut leaves: Vec<[u8; 32]>) -> Result<Self> {
+impl<H: HashBackend> MerkleTree<H> {
+    /// Create a new Merkle tree from leaves using an explicit hash backend.
+    pub fn with_backend(leaves: Vec<[u8; 32]>, backend: H) -> Result<Self> {
         if leaves.is_empty() {
             return Err(CryptoError::MerkleError("Cannot create tree with no leaves".to_string()));
         }
@@ -24,19 +156,7 @@ impl MerkleTree {
 
             for i in (0..current_layer.len()).step_by(2) {
                 if i + 1 < current_layer.len() {
-                    let mut left = current_layer[i];
-                    let mut right = current_layer[i + 1];
-
-                    // Sort for deterministic ordering
-                    if left > right {
-                        std::mem::swap(&mut left, &mut right);
-                    }
-
-                    let mut combined = Vec::with_capacity(64);
-                    combined.extend_from_slice(&left);
-                    combined.extend_from_slice(&right);
-
-                    next_layer.push(keccak256(&combined));
+                    next_layer.push(backend.hash_pair(&current_layer[i], &current_layer[i + 1]));
                 } else {
                     // Odd leaf, promote to next level
                     next_layer.push(current_layer[i]);
@@ -46,7 +166,11 @@ impl MerkleTree {
             layers.push(next_layer);
         }
 
-        Ok(Self { leaves, layers })
+        Ok(Self {
+            leaves,
+            layers,
+            backend,
+        })
     }
 
     /// Get the Merkle root
@@ -54,8 +178,13 @@ impl MerkleTree {
         self.layers.last().unwrap()[0]
     }
 
-    /// Generate Merkle proof for a leaf at given index
-    pub fn generate_proof(&self, index: usize) -> Result<Vec<[u8; 32]>> {
+    /// Generate a Merkle proof for the leaf at `index`.
+    ///
+    /// A layer where `index`'s node was promoted unchanged (no sibling — see `with_backend`)
+    /// contributes no step to the returned proof, so its length can be less than the tree's
+    /// depth. Each step records its [`Position`] explicitly so [`MerkleTree::verify_proof`]
+    /// never has to re-derive left/right from the original index.
+    pub fn generate_proof(&self, index: usize) -> Result<Vec<ProofStep>> {
         if index >= self.leaves.len() {
             return Err(CryptoError::MerkleError(format!(
                 "Index {} out of bounds for {} leaves",
@@ -69,14 +198,21 @@ impl MerkleTree {
 
         for layer_idx in 0..self.layers.len() - 1 {
             let layer = &self.layers[layer_idx];
-            let sibling_index = if current_index % 2 == 0 {
-                current_index + 1
-            } else {
-                current_index - 1
-            };
 
-            if sibling_index < layer.len() {
-                proof.push(layer[sibling_index]);
+            if current_index % 2 == 0 {
+                let sibling_index = current_index + 1;
+                if sibling_index < layer.len() {
+                    proof.push(ProofStep {
+                        sibling: layer[sibling_index],
+                        position: Position::Right,
+                    });
+                }
+            } else {
+                let sibling_index = current_index - 1;
+                proof.push(ProofStep {
+                    sibling: layer[sibling_index],
+                    position: Position::Left,
+                });
             }
 
             current_index /= 2;
@@ -85,31 +221,15 @@ impl MerkleTree {
         Ok(proof)
     }
 
-    /// Verify a Merkle proof
-    pub fn verify_proof(&self, leaf: &[u8; 32], proof: &[[u8; 32]], index: usize) -> bool {
+    /// Verify a Merkle proof produced by [`MerkleTree::generate_proof`].
+    pub fn verify_proof(&self, leaf: &[u8; 32], proof: &[ProofStep]) -> bool {
         let mut computed_hash = *leaf;
-        let mut current_index = index;
-
-        for sibling in proof {
-            let mut left = computed_hash;
-            let mut right = *sibling;
-
-            // Determine order based on index
-            if current_index % 2 != 0 {
-                std::mem::swap(&mut left, &mut right);
-            }
-
-            // Sort for deterministic ordering
-            if left > right {
-                std::mem::swap(&mut left, &mut right);
-            }
 
-            let mut combined = Vec::with_capacity(64);
-            combined.extend_from_slice(&left);
-            combined.extend_from_slice(&right);
-
-            computed_hash = keccak256(&combined);
-            current_index /= 2;
+        for step in proof {
+            computed_hash = match step.position {
+                Position::Left => self.backend.hash_pair(&step.sibling, &computed_hash),
+                Position::Right => self.backend.hash_pair(&computed_hash, &step.sibling),
+            };
         }
 
         computed_hash == self.root()
@@ -131,7 +251,7 @@ pub fn compute_merkle_root(leaves: &[[u8; 32]]) -> Result<[u8; 32]> {
         return Ok(leaves[0]);
     }
 
-    let tree = MerkleTree::new(leaves.to_vec())?;
+    let tree = MerkleTree::<Keccak256Backend>::new(leaves.to_vec())?;
     Ok(tree.root())
 }
 
@@ -139,47 +259,121 @@ pub fn compute_merkle_root(leaves: &[[u8; 32]]) -> Result<[u8; 32]> {
 pub fn generate_merkle_proof(
     leaves: &[[u8; 32]],
     index: usize,
-) -> Result<Vec<[u8; 32]>> {
-    let tree = MerkleTree::new(leaves.to_vec())?;
+) -> Result<Vec<ProofStep>> {
+    let tree = MerkleTree::<Keccak256Backend>::new(leaves.to_vec())?;
     tree.generate_proof(index)
 }
 
 /// Verify a Merkle proof
-pub fn verify_merkle_proof(
-    leaf: &[u8; 32],
-    proof: &[[u8; 32]],
-    root: &[u8; 32],
-    index: usize,
-) -> bool {
+pub fn verify_merkle_proof(leaf: &[u8; 32], proof: &[ProofStep], root: &[u8; 32]) -> bool {
+    let backend = Keccak256Backend;
     let mut computed_hash = *leaf;
-    let mut current_index = index;
 
-    for sibling in proof {
-        let mut left = computed_hash;
-        let mut right = *sibling;
+    for step in proof {
+        computed_hash = match step.position {
+            Position::Left => backend.hash_pair(&step.sibling, &computed_hash),
+            Position::Right => backend.hash_pair(&computed_hash, &step.sibling),
+        };
+    }
 
-        if current_index % 2 != 0 {
-            std::mem::swap(&mut left, &mut right);
-        }
+    computed_hash == *root
+}
 
-        if left > right {
-            std::mem::swap(&mut left, &mut right);
-        }
+/// Pop the next already-known value, preferring `leaves` (original matched leaves, consumed
+/// in tree order) and falling back to previously computed steps in `hashes` (only those
+/// before `limit`, i.e. already produced by an earlier step).
+fn next_known_value(
+    leaves: &[[u8; 32]],
+    hashes: &[[u8; 32]],
+    leaf_pos: &mut usize,
+    hash_pos: &mut usize,
+    limit: usize,
+) -> Option<[u8; 32]> {
+    if *leaf_pos < leaves.len() {
+        let value = leaves[*leaf_pos];
+        *leaf_pos += 1;
+        Some(value)
+    } else if *hash_pos < limit {
+        let value = hashes[*hash_pos];
+        *hash_pos += 1;
+        Some(value)
+    } else {
+        None
+    }
+}
+
+fn combine_sorted(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+    let (left, right) = if a <= b { (a, b) } else { (b, a) };
+
+    let mut combined = Vec::with_capacity(64);
+    combined.extend_from_slice(left);
+    combined.extend_from_slice(right);
+
+    keccak256(&combined)
+}
 
-        let mut combined = Vec::with_capacity(64);
-        combined.extend_from_slice(&left);
-        combined.extend_from_slice(&right);
+/// Verify a multiproof against a root.
+///
+/// `leaves` must be supplied in tree order (ascending index, i.e. the order
+/// [`MerkleTree::generate_multiproof`] visited them), and correspond 1:1 with the indices
+/// originally passed to it. Always combines with [`combine_sorted`] (sorted-pair Keccak), so
+/// it only verifies multiproofs generated over a Keccak-backed tree (the only backend
+/// [`MerkleTree::generate_multiproof`] is available on). Returns `false` on any malformed
+/// input, including cursor overflow caused by a mismatched `proof`/`proof_flags` pair, or
+/// leftover `leaves`/`proof` entries the proof never consumed.
+pub fn verify_multiproof(
+    leaves: &[[u8; 32]],
+    proof: &[[u8; 32]],
+    proof_flags: &[MultiProofFlag],
+    root: &[u8; 32],
+) -> bool {
+    let total = proof_flags.len();
 
-        computed_hash = keccak256(&combined);
-        current_index /= 2;
+    if total == 0 {
+        return leaves.len() == 1 && leaves[0] == *root;
     }
 
-    computed_hash == *root
+    let mut hashes = vec![[0u8; 32]; total];
+    let mut leaf_pos = 0;
+    let mut hash_pos = 0;
+    let mut proof_pos = 0;
+
+    for i in 0..total {
+        let a = match next_known_value(leaves, &hashes, &mut leaf_pos, &mut hash_pos, i) {
+            Some(value) => value,
+            None => return false,
+        };
+
+        hashes[i] = match proof_flags[i] {
+            MultiProofFlag::Carry => a,
+            MultiProofFlag::Known => {
+                match next_known_value(leaves, &hashes, &mut leaf_pos, &mut hash_pos, i) {
+                    Some(b) => combine_sorted(&a, &b),
+                    None => return false,
+                }
+            }
+            MultiProofFlag::Proof => {
+                if proof_pos >= proof.len() {
+                    return false;
+                }
+                let b = proof[proof_pos];
+                proof_pos += 1;
+                combine_sorted(&a, &b)
+            }
+        };
+    }
+
+    if leaf_pos != leaves.len() || proof_pos != proof.len() {
+        return false;
+    }
+
+    hashes[total - 1] == *root
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::hash_backend::PoseidonBackend;
     use crate::hashing::keccak256;
 
     #[test]
@@ -200,7 +394,7 @@ mod tests {
 
         // Verify proof for first leaf
         let proof = tree.generate_proof(0).unwrap();
-        assert!(tree.verify_proof(&leaves[0], &proof, 0));
+        assert!(tree.verify_proof(&leaves[0], &proof));
     }
 
     #[test]
@@ -212,7 +406,7 @@ mod tests {
         let root = compute_merkle_root(&leaves).unwrap();
         let proof = generate_merkle_proof(&leaves, 3).unwrap();
 
-        assert!(verify_merkle_proof(&leaves[3], &proof, &root, 3));
+        assert!(verify_merkle_proof(&leaves[3], &proof, &root));
     }
 
     #[test]
@@ -225,8 +419,131 @@ mod tests {
         
         for i in 0..leaves.len() {
             let proof = tree.generate_proof(i).unwrap();
-            assert!(tree.verify_proof(&leaves[i], &proof, i));
+            assert!(tree.verify_proof(&leaves[i], &proof));
+        }
+    }
+
+    #[test]
+    fn test_multiproof_subset_of_leaves() {
+        let leaves: Vec<[u8; 32]> = (0..8)
+            .map(|i| keccak256(format!("tx{}", i).as_bytes()))
+            .collect();
+
+        let tree = MerkleTree::new(leaves.clone()).unwrap();
+        let indices = [1, 3, 6];
+
+        let (proof, proof_flags) = tree.generate_multiproof(&indices).unwrap();
+        let proof_leaves: Vec<[u8; 32]> = indices.iter().map(|&i| leaves[i]).collect();
+
+        assert!(verify_multiproof(&proof_leaves, &proof, &proof_flags, &tree.root()));
+    }
+
+    #[test]
+    fn test_multiproof_all_leaves_needs_no_proof() {
+        let leaves: Vec<[u8; 32]> = (0..4)
+            .map(|i| keccak256(format!("tx{}", i).as_bytes()))
+            .collect();
+
+        let tree = MerkleTree::new(leaves.clone()).unwrap();
+        let indices: Vec<usize> = (0..leaves.len()).collect();
+
+        let (proof, proof_flags) = tree.generate_multiproof(&indices).unwrap();
+        assert!(proof.is_empty());
+        assert!(verify_multiproof(&leaves, &proof, &proof_flags, &tree.root()));
+    }
+
+    #[test]
+    fn test_multiproof_over_odd_sized_tree_verifies_against_correct_root() {
+        for leaf_count in [5usize, 7] {
+            let leaves: Vec<[u8; 32]> = (0..leaf_count)
+                .map(|i| keccak256(format!("tx{}", i).as_bytes()))
+                .collect();
+
+            let tree = MerkleTree::new(leaves.clone()).unwrap();
+            let indices: Vec<usize> = (0..leaf_count).step_by(2).collect();
+
+            let (proof, proof_flags) = tree.generate_multiproof(&indices).unwrap();
+            let proof_leaves: Vec<[u8; 32]> = indices.iter().map(|&i| leaves[i]).collect();
+
+            assert!(
+                verify_multiproof(&proof_leaves, &proof, &proof_flags, &tree.root()),
+                "multiproof over a {}-leaf tree should verify against its own root",
+                leaf_count
+            );
         }
     }
+
+    #[test]
+    fn test_multiproof_rejects_extra_leaf() {
+        // An extra leaf tacked onto the end is drawn ahead of the computed hashes (leaves are
+        // always consumed first), which shifts every later step, so the reconstructed root no
+        // longer matches.
+        let leaves: Vec<[u8; 32]> = (0..8)
+            .map(|i| keccak256(format!("tx{}", i).as_bytes()))
+            .collect();
+
+        let tree = MerkleTree::new(leaves.clone()).unwrap();
+        let indices = [1, 3, 6];
+
+        let (proof, proof_flags) = tree.generate_multiproof(&indices).unwrap();
+        let mut proof_leaves: Vec<[u8; 32]> = indices.iter().map(|&i| leaves[i]).collect();
+        proof_leaves.push(leaves[0]);
+
+        assert!(!verify_multiproof(&proof_leaves, &proof, &proof_flags, &tree.root()));
+    }
+
+    #[test]
+    fn test_multiproof_rejects_wrong_root() {
+        let leaves: Vec<[u8; 32]> = (0..5)
+            .map(|i| keccak256(format!("tx{}", i).as_bytes()))
+            .collect();
+
+        let tree = MerkleTree::new(leaves.clone()).unwrap();
+        let indices = [0, 4];
+
+        let (proof, proof_flags) = tree.generate_multiproof(&indices).unwrap();
+        let proof_leaves: Vec<[u8; 32]> = indices.iter().map(|&i| leaves[i]).collect();
+        let wrong_root = keccak256(b"not the root");
+
+        assert!(!verify_multiproof(&proof_leaves, &proof, &proof_flags, &wrong_root));
+    }
+
+    #[test]
+    fn test_merkle_tree_with_poseidon_backend() {
+        let leaves: Vec<[u8; 32]> = (0..4)
+            .map(|i| keccak256(format!("tx{}", i).as_bytes()))
+            .collect();
+
+        let tree =
+            MerkleTree::with_backend(leaves.clone(), PoseidonBackend::default()).unwrap();
+
+        for i in 0..leaves.len() {
+            let proof = tree.generate_proof(i).unwrap();
+            assert!(tree.verify_proof(&leaves[i], &proof));
+        }
+    }
+
+    #[test]
+    fn test_merkle_tree_with_poseidon_backend_over_odd_leaf_count() {
+        // Poseidon's `hash_pair` is order-sensitive (unlike Keccak, which sorts), so a proof
+        // over a tree with a promoted (unpaired) node only verifies if left/right is tracked
+        // explicitly rather than inferred from the leaf's original index.
+        let leaves: Vec<[u8; 32]> = (0..5)
+            .map(|i| keccak256(format!("tx{}", i).as_bytes()))
+            .collect();
+
+        let tree =
+            MerkleTree::with_backend(leaves.clone(), PoseidonBackend::default()).unwrap();
+
+        for i in 0..leaves.len() {
+            let proof = tree.generate_proof(i).unwrap();
+            assert!(tree.verify_proof(&leaves[i], &proof), "leaf {} should verify", i);
+        }
+
+        // The promoted trailing leaf specifically, called out since it's the case that
+        // exposed the original bug.
+        let proof = tree.generate_proof(4).unwrap();
+        assert!(tree.verify_proof(&leaves[4], &proof));
+    }
 }
 
@@ -0,0 +1,353 @@
+//! Ethereum-style Merkle Patricia Trie proof verification.
+//!
+//! Lets a verifier confirm that a payer holds a claimed balance (or that a storage slot
+//! holds a claimed value) against a block's `stateRoot`, without trusting the facilitator
+//! that supplied the proof.
+
+use crate::errors::{CryptoError, Result};
+use crate::hashing::keccak256;
+use crate::rlp::{self, RlpItem};
+
+/// Decoded Ethereum account state, as stored in a state trie leaf.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccountState {
+    pub nonce: u64,
+    /// Big-endian wei balance, left-padded to 32 bytes.
+    pub balance: [u8; 32],
+    pub storage_root: [u8; 32],
+    pub code_hash: [u8; 32],
+}
+
+/// Verify an account inclusion/exclusion proof against a block's `stateRoot`.
+///
+/// `proof_nodes` are the RLP-encoded trie nodes from root to leaf, e.g. as returned by
+/// `eth_getProof`. Returns `Ok(Some(account))` if the address is present, `Ok(None)` if the
+/// proof demonstrates the address is absent, and `Err` if the nodes don't chain to
+/// `state_root`.
+pub fn verify_account_proof(
+    state_root: &[u8; 32],
+    address: &[u8; 20],
+    proof_nodes: &[Vec<u8>],
+) -> Result<Option<AccountState>> {
+    let key = keccak256(address);
+    match walk_trie(state_root, &key, proof_nodes)? {
+        None => Ok(None),
+        Some(value) => Ok(Some(decode_account(&value)?)),
+    }
+}
+
+/// Verify a storage slot inclusion/exclusion proof against a contract's `storageRoot`.
+pub fn verify_storage_proof(
+    storage_root: &[u8; 32],
+    slot: &[u8; 32],
+    proof_nodes: &[Vec<u8>],
+) -> Result<Option<[u8; 32]>> {
+    let key = keccak256(slot);
+    match walk_trie(storage_root, &key, proof_nodes)? {
+        None => Ok(None),
+        Some(value) => {
+            let (item, _) = rlp::decode(&value)?;
+            Ok(Some(bytes_to_word(item.as_string()?)?))
+        }
+    }
+}
+
+/// A reference to a child node: either its `keccak256` hash (the common case, looked up in
+/// `proof_nodes`) or the node itself, embedded inline.
+///
+/// The trie encoding embeds a child directly, instead of hashing it, whenever its RLP
+/// encoding is shorter than 32 bytes — common in small storage tries. An inline child is
+/// already covered by its parent's own hash check, so it's decoded straight from the parent
+/// without consuming another entry from `proof_nodes`.
+enum ChildRef {
+    Hash([u8; 32]),
+    Inline(RlpItem),
+}
+
+fn child_ref(item: &RlpItem) -> Result<ChildRef> {
+    match item {
+        RlpItem::List(_) => Ok(ChildRef::Inline(item.clone())),
+        RlpItem::String(bytes) if bytes.len() == 32 => {
+            let mut hash = [0u8; 32];
+            hash.copy_from_slice(bytes);
+            Ok(ChildRef::Hash(hash))
+        }
+        RlpItem::String(bytes) => Err(CryptoError::MerkleError(format!(
+            "expected a 32-byte child hash reference, found {} bytes",
+            bytes.len()
+        ))),
+    }
+}
+
+/// Walk a Merkle Patricia Trie from `root` along the nibble path of `key`, verifying each
+/// proof node's hash against the reference supplied by its parent.
+///
+/// Returns the RLP-encoded value at the matching leaf, or `None` if the proof terminates at
+/// a divergent path or an empty branch slot (exclusion).
+fn walk_trie(root: &[u8; 32], key: &[u8; 32], proof_nodes: &[Vec<u8>]) -> Result<Option<Vec<u8>>> {
+    let nibbles = to_nibbles(key);
+    let mut nibble_pos = 0;
+    let mut next_ref = ChildRef::Hash(*root);
+    let mut nodes = proof_nodes.iter();
+
+    loop {
+        let node = match next_ref {
+            ChildRef::Hash(expected_hash) => {
+                let node_bytes = nodes.next().ok_or_else(|| {
+                    CryptoError::MerkleError("MPT proof ended before resolving key".to_string())
+                })?;
+
+                if keccak256(node_bytes) != expected_hash {
+                    return Err(CryptoError::MerkleError(
+                        "MPT proof node hash does not match expected reference".to_string(),
+                    ));
+                }
+
+                rlp::decode(node_bytes)?.0
+            }
+            ChildRef::Inline(item) => item,
+        };
+
+        let items = node.as_list()?;
+
+        match items.len() {
+            17 => {
+                // Branch node: 16 child slots keyed by nibble, plus a value slot.
+                if nibble_pos == nibbles.len() {
+                    let value = items[16].as_string()?;
+                    return Ok(if value.is_empty() {
+                        None
+                    } else {
+                        Some(value.to_vec())
+                    });
+                }
+
+                let child = &items[nibbles[nibble_pos] as usize];
+                if matches!(child, RlpItem::String(bytes) if bytes.is_empty()) {
+                    return Ok(None); // Exclusion: branch slot for this nibble is empty.
+                }
+
+                nibble_pos += 1;
+                next_ref = child_ref(child)?;
+            }
+            2 => {
+                let (path, is_leaf) = decode_compact(items[0].as_string()?)?;
+                let remaining = &nibbles[nibble_pos..];
+
+                if is_leaf {
+                    return Ok(if remaining == path.as_slice() {
+                        Some(items[1].as_string()?.to_vec())
+                    } else {
+                        None // Exclusion: leaf's remaining path diverges from the key.
+                    });
+                }
+
+                if !remaining.starts_with(path.as_slice()) {
+                    return Ok(None); // Exclusion: extension's shared path diverges.
+                }
+
+                nibble_pos += path.len();
+                next_ref = child_ref(&items[1])?;
+            }
+            _ => {
+                return Err(CryptoError::MerkleError(format!(
+                    "malformed MPT node with {} items",
+                    items.len()
+                )))
+            }
+        }
+    }
+}
+
+/// Decode a hex-prefix (compact) encoded nibble path, returning the nibbles and whether the
+/// node is a leaf (vs. an extension).
+fn decode_compact(encoded: &[u8]) -> Result<(Vec<u8>, bool)> {
+    let first = *encoded
+        .first()
+        .ok_or_else(|| CryptoError::MerkleError("empty compact-encoded path".to_string()))?;
+
+    let flag = first >> 4;
+    let is_leaf = flag == 2 || flag == 3;
+    let is_odd = flag == 1 || flag == 3;
+
+    let mut nibbles = Vec::with_capacity(encoded.len() * 2);
+    if is_odd {
+        nibbles.push(first & 0x0f);
+    }
+    for &byte in &encoded[1..] {
+        nibbles.push(byte >> 4);
+        nibbles.push(byte & 0x0f);
+    }
+
+    Ok((nibbles, is_leaf))
+}
+
+fn to_nibbles(key: &[u8]) -> Vec<u8> {
+    let mut nibbles = Vec::with_capacity(key.len() * 2);
+    for &byte in key {
+        nibbles.push(byte >> 4);
+        nibbles.push(byte & 0x0f);
+    }
+    nibbles
+}
+
+fn decode_account(encoded: &[u8]) -> Result<AccountState> {
+    let (item, _) = rlp::decode(encoded)?;
+    let fields = item.as_list()?;
+
+    if fields.len() != 4 {
+        return Err(CryptoError::MerkleError(format!(
+            "expected 4 account fields, found {}",
+            fields.len()
+        )));
+    }
+
+    Ok(AccountState {
+        nonce: bytes_to_u64(fields[0].as_string()?),
+        balance: bytes_to_word(fields[1].as_string()?)?,
+        storage_root: bytes_to_hash(fields[2].as_string()?)?,
+        code_hash: bytes_to_hash(fields[3].as_string()?)?,
+    })
+}
+
+fn bytes_to_u64(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0u64, |acc, &b| (acc << 8) | b as u64)
+}
+
+fn bytes_to_word(bytes: &[u8]) -> Result<[u8; 32]> {
+    if bytes.len() > 32 {
+        return Err(CryptoError::MerkleError(
+            "value exceeds 32 bytes".to_string(),
+        ));
+    }
+    let mut word = [0u8; 32];
+    word[32 - bytes.len()..].copy_from_slice(bytes);
+    Ok(word)
+}
+
+fn bytes_to_hash(bytes: &[u8]) -> Result<[u8; 32]> {
+    if bytes.len() != 32 {
+        return Err(CryptoError::MerkleError(format!(
+            "expected a 32-byte hash, found {} bytes",
+            bytes.len()
+        )));
+    }
+    let mut hash = [0u8; 32];
+    hash.copy_from_slice(bytes);
+    Ok(hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode_string(bytes: &[u8]) -> Vec<u8> {
+        if bytes.len() == 1 && bytes[0] < 0x80 {
+            return bytes.to_vec();
+        }
+        let mut out = vec![0x80 + bytes.len() as u8];
+        out.extend_from_slice(bytes);
+        out
+    }
+
+    fn encode_list(items: &[Vec<u8>]) -> Vec<u8> {
+        let body: Vec<u8> = items.concat();
+        let mut out = vec![0xc0 + body.len() as u8];
+        out.extend_from_slice(&body);
+        out
+    }
+
+    fn compact_leaf(nibbles: &[u8]) -> Vec<u8> {
+        let mut out = Vec::new();
+        let odd = nibbles.len() % 2 == 1;
+        let mut iter = nibbles.iter();
+        if odd {
+            out.push(0x30 | iter.next().unwrap());
+        } else {
+            out.push(0x20);
+        }
+        let rest: Vec<u8> = iter.copied().collect();
+        for pair in rest.chunks(2) {
+            out.push((pair[0] << 4) | pair[1]);
+        }
+        out
+    }
+
+    #[test]
+    fn test_storage_proof_single_leaf_trie() {
+        let slot = [7u8; 32];
+        let key_nibbles: Vec<u8> = keccak256(&slot)
+            .iter()
+            .flat_map(|b| vec![b >> 4, b & 0x0f])
+            .collect();
+
+        // The storage trie stores storage values double RLP-encoded: the leaf's value field
+        // is itself an RLP string wrapping the RLP encoding of the raw value.
+        let raw_value = encode_string(&[0xaa, 0xbb, 0xcc]);
+        let value_field = encode_string(&raw_value);
+        let leaf_node = encode_list(&[encode_string(&compact_leaf(&key_nibbles)), value_field]);
+        let storage_root = keccak256(&leaf_node);
+
+        let result = verify_storage_proof(&storage_root, &slot, &[leaf_node]).unwrap();
+        assert_eq!(result, Some(bytes_to_word(&[0xaa, 0xbb, 0xcc]).unwrap()));
+    }
+
+    #[test]
+    fn test_storage_proof_wrong_root_errors() {
+        let slot = [7u8; 32];
+        let key_nibbles: Vec<u8> = keccak256(&slot)
+            .iter()
+            .flat_map(|b| vec![b >> 4, b & 0x0f])
+            .collect();
+
+        let value = encode_string(&[0x01]);
+        let leaf_node = encode_list(&[encode_string(&compact_leaf(&key_nibbles)), value]);
+        let wrong_root = [0u8; 32];
+
+        assert!(verify_storage_proof(&wrong_root, &slot, &[leaf_node]).is_err());
+    }
+
+    #[test]
+    fn test_storage_proof_with_inline_child_node() {
+        let slot = [9u8; 32];
+        let key_nibbles: Vec<u8> = keccak256(&slot)
+            .iter()
+            .flat_map(|b| vec![b >> 4, b & 0x0f])
+            .collect();
+
+        // A branch whose child node is embedded directly as an RLP list rather than referenced
+        // by keccak256 hash. With its 63-nibble path this leaf encodes to 35 bytes, more than a
+        // real trie would inline, but the verifier decodes any embedded list the same way.
+        let branch_nibble = key_nibbles[0] as usize;
+        let remaining = &key_nibbles[1..];
+
+        let raw_value = encode_string(&[0x42]);
+        let value_field = encode_string(&raw_value);
+        let inline_leaf = encode_list(&[encode_string(&compact_leaf(remaining)), value_field]);
+
+        let mut branch_items: Vec<Vec<u8>> = (0..16)
+            .map(|i| {
+                if i == branch_nibble {
+                    inline_leaf.clone()
+                } else {
+                    encode_string(&[])
+                }
+            })
+            .collect();
+        branch_items.push(encode_string(&[])); // value slot: unused, key resolves via the leaf
+
+        let branch_node = encode_list(&branch_items);
+        let storage_root = keccak256(&branch_node);
+
+        let result = verify_storage_proof(&storage_root, &slot, &[branch_node]).unwrap();
+        assert_eq!(result, Some(bytes_to_word(&[0x42]).unwrap()));
+    }
+
+    #[test]
+    fn test_decode_compact_leaf_even_length() {
+        let encoded = compact_leaf(&[1, 2, 3, 4]);
+        let (nibbles, is_leaf) = decode_compact(&encoded).unwrap();
+        assert!(is_leaf);
+        assert_eq!(nibbles, vec![1, 2, 3, 4]);
+    }
+}
@@ -0,0 +1,195 @@
+//! Poseidon: a sponge-based hash over a prime field, used for ZK-friendly and Starknet-style
+//! Merkle roots where Keccak is expensive to verify in-circuit.
+//!
+//! The permutation is generic over a caller-supplied [`PoseidonConfig`] (modulus, round
+//! constants, MDS matrix, round counts) so different curves' scalar fields can be plugged
+//! into [`crate::hash_backend::PoseidonBackend`].
+//!
+//! [`PoseidonConfig::unaudited_bn254_placeholder`] is the only config this crate ships, and it
+//! is not interoperable with any real Poseidon instance (Starknet's, circomlib's, or otherwise)
+//! — its round constants and MDS matrix are derived deterministically rather than taken from
+//! an audited parameter set. Don't use it to produce roots that need to match a ZK circuit or
+//! another implementation; supply a real [`PoseidonConfig`] for that.
+
+use crate::fr::{self, Limbs};
+use crate::hashing::keccak256;
+
+/// State width: one capacity element plus a two-element rate, enough to absorb a sibling
+/// pair and squeeze a single digest.
+const WIDTH: usize = 3;
+
+/// Poseidon permutation parameters.
+///
+/// [`PoseidonConfig::unaudited_bn254_placeholder`] derives a config deterministically from a
+/// label rather than the audited Poseidon reference parameters — swap in curve-specific
+/// audited constants via this struct before relying on this for anything beyond prototyping.
+#[derive(Debug, Clone)]
+pub struct PoseidonConfig {
+    pub modulus: Limbs,
+    pub round_constants: Vec<[Limbs; WIDTH]>,
+    pub mds: [[Limbs; WIDTH]; WIDTH],
+    pub full_rounds: usize,
+    pub partial_rounds: usize,
+}
+
+impl PoseidonConfig {
+    /// A deterministic placeholder configuration over the BN254 scalar field.
+    ///
+    /// Named `unaudited` deliberately: its round constants are derived from a keccak-based
+    /// label (see `derive_constant`) and its MDS matrix is an arbitrary Cauchy matrix, neither
+    /// taken from the Poseidon paper's Grain LFSR generator or any published parameter set.
+    /// Digests produced with this config will not match Starknet's Poseidon, circomlib's, or
+    /// any other real implementation — it exists so [`crate::hash_backend::PoseidonBackend`]
+    /// has a working default for prototyping an order-sensitive backend, not for interop.
+    pub fn unaudited_bn254_placeholder() -> Self {
+        // BN254 scalar field modulus.
+        let modulus = fr::from_be_bytes(&[
+            0x30, 0x64, 0x4e, 0x72, 0xe1, 0x31, 0xa0, 0x29, 0xb8, 0x50, 0x45, 0xb6, 0x81, 0x81,
+            0x58, 0x5d, 0x28, 0x33, 0xe8, 0x48, 0x79, 0xb9, 0x70, 0x91, 0x43, 0xe1, 0xf5, 0x93,
+            0xf0, 0x00, 0x00, 0x01,
+        ]);
+
+        let full_rounds = 8;
+        let partial_rounds = 57;
+        let round_constants = (0..full_rounds + partial_rounds)
+            .map(|round| {
+                std::array::from_fn(|i| {
+                    derive_constant(&modulus, "nova402-poseidon-bn254-rc", round, i)
+                })
+            })
+            .collect();
+
+        // A Cauchy matrix (mds[i][j] = 1 / (x_i + y_j), with distinct x and y) is always
+        // invertible, which is what Poseidon needs from its MDS layer.
+        let xs = [1u64, 2, 3];
+        let ys = [4u64, 5, 6];
+        let mds = std::array::from_fn(|i| {
+            std::array::from_fn(|j| {
+                let sum = fr::add_mod(&[xs[i], 0, 0, 0], &[ys[j], 0, 0, 0], &modulus);
+                fr::inv_mod(&sum, &modulus)
+            })
+        });
+
+        Self {
+            modulus,
+            round_constants,
+            mds,
+            full_rounds,
+            partial_rounds,
+        }
+    }
+}
+
+/// Derive a round constant deterministically from a label, round index, and state position
+/// by reducing a Keccak-256 digest into the field. Good enough for a placeholder config;
+/// audited parameter sets should be generated per the Poseidon paper's Grain LFSR instead.
+fn derive_constant(modulus: &Limbs, label: &str, round: usize, position: usize) -> Limbs {
+    let preimage = format!("{label}:{round}:{position}");
+    let digest = keccak256(preimage.as_bytes());
+    fr::reduce(&fr::from_be_bytes(&digest), modulus)
+}
+
+fn pow5(a: &Limbs, modulus: &Limbs) -> Limbs {
+    let a2 = fr::mul_mod(a, a, modulus);
+    let a4 = fr::mul_mod(&a2, &a2, modulus);
+    fr::mul_mod(&a4, a, modulus)
+}
+
+fn apply_mds(state: &[Limbs; WIDTH], config: &PoseidonConfig) -> [Limbs; WIDTH] {
+    std::array::from_fn(|i| {
+        config.mds[i].iter().zip(state.iter()).fold([0u64; 4], |acc, (coeff, s)| {
+            let term = fr::mul_mod(coeff, s, &config.modulus);
+            fr::add_mod(&acc, &term, &config.modulus)
+        })
+    })
+}
+
+fn add_round_constants(state: &mut [Limbs; WIDTH], config: &PoseidonConfig, round: usize) {
+    for (s, rc) in state.iter_mut().zip(config.round_constants[round].iter()) {
+        *s = fr::add_mod(s, rc, &config.modulus);
+    }
+}
+
+/// Run the Poseidon permutation in place over `state`.
+fn permute(state: &mut [Limbs; WIDTH], config: &PoseidonConfig) {
+    let half_full = config.full_rounds / 2;
+    let mut round = 0;
+
+    for _ in 0..half_full {
+        add_round_constants(state, config, round);
+        for s in state.iter_mut() {
+            *s = pow5(s, &config.modulus);
+        }
+        *state = apply_mds(state, config);
+        round += 1;
+    }
+
+    for _ in 0..config.partial_rounds {
+        add_round_constants(state, config, round);
+        state[0] = pow5(&state[0], &config.modulus);
+        *state = apply_mds(state, config);
+        round += 1;
+    }
+
+    for _ in 0..half_full {
+        add_round_constants(state, config, round);
+        for s in state.iter_mut() {
+            *s = pow5(s, &config.modulus);
+        }
+        *state = apply_mds(state, config);
+        round += 1;
+    }
+}
+
+/// Absorb `left` and `right` and squeeze a single field element as the digest.
+pub fn hash_two(left: &[u8; 32], right: &[u8; 32], config: &PoseidonConfig) -> [u8; 32] {
+    let mut state = [
+        [0u64, 0, 0, 0],
+        fr::reduce(&fr::from_be_bytes(left), &config.modulus),
+        fr::reduce(&fr::from_be_bytes(right), &config.modulus),
+    ];
+
+    permute(&mut state, config);
+
+    fr::to_be_bytes(&state[0])
+}
+
+/// Absorb a single value (padding the rate with zero) and squeeze a digest, used for
+/// hashing raw leaf data.
+pub fn hash_one(value: &[u8; 32], config: &PoseidonConfig) -> [u8; 32] {
+    hash_two(value, &[0u8; 32], config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hash_two_is_deterministic() {
+        let config = PoseidonConfig::unaudited_bn254_placeholder();
+        let a = [1u8; 32];
+        let b = [2u8; 32];
+
+        assert_eq!(hash_two(&a, &b, &config), hash_two(&a, &b, &config));
+    }
+
+    #[test]
+    fn test_hash_two_is_order_sensitive() {
+        let config = PoseidonConfig::unaudited_bn254_placeholder();
+        let a = [1u8; 32];
+        let b = [2u8; 32];
+
+        assert_ne!(hash_two(&a, &b, &config), hash_two(&b, &a, &config));
+    }
+
+    #[test]
+    fn test_hash_two_differs_from_inputs() {
+        let config = PoseidonConfig::unaudited_bn254_placeholder();
+        let a = [3u8; 32];
+        let b = [4u8; 32];
+
+        let digest = hash_two(&a, &b, &config);
+        assert_ne!(digest, a);
+        assert_ne!(digest, b);
+    }
+}
@@ -0,0 +1,147 @@
+//! Minimal RLP (Recursive Length Prefix) decoding, as used by Ethereum's Merkle Patricia Trie.
+
+use crate::errors::{CryptoError, Result};
+
+/// A decoded RLP item: either a byte string or a list of further RLP items.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RlpItem {
+    String(Vec<u8>),
+    List(Vec<RlpItem>),
+}
+
+impl RlpItem {
+    /// Borrow this item as a byte string, erroring if it's a list.
+    pub fn as_string(&self) -> Result<&[u8]> {
+        match self {
+            RlpItem::String(bytes) => Ok(bytes),
+            RlpItem::List(_) => Err(CryptoError::MerkleError(
+                "expected RLP string, found list".to_string(),
+            )),
+        }
+    }
+
+    /// Borrow this item as a list, erroring if it's a string.
+    pub fn as_list(&self) -> Result<&[RlpItem]> {
+        match self {
+            RlpItem::List(items) => Ok(items),
+            RlpItem::String(_) => Err(CryptoError::MerkleError(
+                "expected RLP list, found string".to_string(),
+            )),
+        }
+    }
+}
+
+/// Decode a single top-level RLP item from `data`, returning it along with the number of
+/// bytes consumed from the front of `data`.
+pub fn decode(data: &[u8]) -> Result<(RlpItem, usize)> {
+    if data.is_empty() {
+        return Err(CryptoError::MerkleError(
+            "cannot RLP-decode empty input".to_string(),
+        ));
+    }
+
+    let prefix = data[0];
+
+    match prefix {
+        0x00..=0x7f => Ok((RlpItem::String(vec![prefix]), 1)),
+        0x80..=0xb7 => {
+            let len = (prefix - 0x80) as usize;
+            let bytes = read_bytes(data, 1, len)?;
+            Ok((RlpItem::String(bytes.to_vec()), 1 + len))
+        }
+        0xb8..=0xbf => {
+            let len_of_len = (prefix - 0xb7) as usize;
+            let len = read_length(data, 1, len_of_len)?;
+            let bytes = read_bytes(data, 1 + len_of_len, len)?;
+            Ok((RlpItem::String(bytes.to_vec()), 1 + len_of_len + len))
+        }
+        0xc0..=0xf7 => {
+            let len = (prefix - 0xc0) as usize;
+            let body = read_bytes(data, 1, len)?;
+            let items = decode_list_body(body)?;
+            Ok((RlpItem::List(items), 1 + len))
+        }
+        0xf8..=0xff => {
+            let len_of_len = (prefix - 0xf7) as usize;
+            let len = read_length(data, 1, len_of_len)?;
+            let body = read_bytes(data, 1 + len_of_len, len)?;
+            let items = decode_list_body(body)?;
+            Ok((RlpItem::List(items), 1 + len_of_len + len))
+        }
+    }
+}
+
+fn read_bytes(data: &[u8], offset: usize, len: usize) -> Result<&[u8]> {
+    data.get(offset..offset + len)
+        .ok_or_else(|| CryptoError::MerkleError("RLP input truncated".to_string()))
+}
+
+fn read_length(data: &[u8], offset: usize, len_of_len: usize) -> Result<usize> {
+    let bytes = read_bytes(data, offset, len_of_len)?;
+    let mut len = 0usize;
+    for &b in bytes {
+        len = (len << 8) | b as usize;
+    }
+    Ok(len)
+}
+
+fn decode_list_body(mut body: &[u8]) -> Result<Vec<RlpItem>> {
+    let mut items = Vec::new();
+    while !body.is_empty() {
+        let (item, consumed) = decode(body)?;
+        items.push(item);
+        body = &body[consumed..];
+    }
+    Ok(items)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode_string(bytes: &[u8]) -> Vec<u8> {
+        if bytes.len() == 1 && bytes[0] < 0x80 {
+            return bytes.to_vec();
+        }
+        let mut out = vec![0x80 + bytes.len() as u8];
+        out.extend_from_slice(bytes);
+        out
+    }
+
+    fn encode_list(items: &[Vec<u8>]) -> Vec<u8> {
+        let body: Vec<u8> = items.concat();
+        let mut out = vec![0xc0 + body.len() as u8];
+        out.extend_from_slice(&body);
+        out
+    }
+
+    #[test]
+    fn test_decode_single_byte() {
+        let (item, consumed) = decode(&[0x42]).unwrap();
+        assert_eq!(item.as_string().unwrap(), &[0x42]);
+        assert_eq!(consumed, 1);
+    }
+
+    #[test]
+    fn test_decode_short_string() {
+        let encoded = encode_string(b"hello");
+        let (item, consumed) = decode(&encoded).unwrap();
+        assert_eq!(item.as_string().unwrap(), b"hello");
+        assert_eq!(consumed, encoded.len());
+    }
+
+    #[test]
+    fn test_decode_list_of_strings() {
+        let encoded = encode_list(&[encode_string(b"cat"), encode_string(b"dog")]);
+        let (item, consumed) = decode(&encoded).unwrap();
+        let items = item.as_list().unwrap();
+        assert_eq!(items[0].as_string().unwrap(), b"cat");
+        assert_eq!(items[1].as_string().unwrap(), b"dog");
+        assert_eq!(consumed, encoded.len());
+    }
+
+    #[test]
+    fn test_decode_empty_input_errors() {
+        assert!(decode(&[]).is_err());
+    }
+}